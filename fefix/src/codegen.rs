@@ -1,47 +1,72 @@
 //! Code generation utilities.
 
 use super::data_type::DataType;
-use super::dictionary::{Dictionary, Field, LayoutItem, LayoutItemKind, Message};
+use super::dictionary::{Component, Dictionary, Field, LayoutItem, LayoutItemKind, Message};
 use super::TagU16;
 use heck::{CamelCase, ShoutySnakeCase, SnakeCase};
 use indoc::indoc;
 
+/// Controls how FIX `<component>` references are expanded into generated
+/// message (and group-entry) structs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentEncoding {
+    /// Generate one struct per component and reference it by type from
+    /// every struct that uses it.
+    Nested,
+    /// Splice the component's own fields directly into the struct that
+    /// references it, as if they had been listed there verbatim.
+    Flatten,
+}
+
 fn generated_code_notice() -> String {
-    use chrono::prelude::*;
     format!(
         indoc!(
             r#"
-            // Generated automatically by FerrumFIX {} on {}.
+            // Generated automatically by FerrumFIX {}.
             //
             // DO NOT MODIFY MANUALLY.
-            // DO NOT COMMIT TO VERSION CONTROL.
-            // ALL CHANGES WILL BE OVERWRITTEN.
+            // Run the `sourcegen_*` tests in `codegen.rs` to regenerate.
             "#
         ),
         FEFIX_VERSION,
-        Utc::now().to_rfc2822(),
     )
 }
 
 const FEFIX_VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
-pub fn message(dict: Dictionary, message: Message, custom_derive_line: &str) -> String {
+/// Builds the struct for a `<message>`, together with the entry structs
+/// of the repeating groups its layout needs. Component structs are not
+/// emitted here: in [`ComponentEncoding::Nested`] mode they come from
+/// [`fields`] and are only referenced by type.
+///
+/// Every field the `ReadFields` derive is expected to drive gets a
+/// `#[fefix(tag = ..., required = ...)]` or `#[fefix(component)]`
+/// attribute (see `Dictionary::translate_layout_item_to_struct_field`).
+/// The `lifetime: PhantomData<&'a ()>` field isn't FIX data at all — it
+/// only exists to anchor the struct's `'a` parameter when a message has
+/// no borrowing fields of its own — so it's marked `#[fefix(skip)]`
+/// rather than carrying a tag attribute, exempting it from the derive's
+/// per-field requirements. `Dictionary::build_group_entry_struct` and
+/// `Dictionary::build_component_struct` do the same for their own
+/// phantom field.
+pub fn message(
+    dict: Dictionary,
+    message: Message,
+    custom_derive_line: &str,
+    component_encoding: ComponentEncoding,
+) -> String {
     let identifier = message.name().to_camel_case();
-    let fields = message
-        .layout()
-        .map(|layout_item| {
-            dict.translate_layout_item_to_struct_field(&layout_item, layout_item.required())
-        })
-        .filter(|opt| opt.is_some())
-        .map(|opt| opt.unwrap())
-        .collect::<Vec<String>>()
-        .join("\n");
+    let (fields, aux_structs) =
+        dict.expand_layout_items(&identifier, message.layout(), component_encoding);
     format!(
         indoc!(
             r#"
-            #[derive(Debug)]
+            {aux_structs}
+            #[derive(Debug, Clone, ReadFields)]
+            #[fefix(msg_type = "{msg_type}")]
             {custom_derive_line}
             pub struct {identifier}<'a> {{
+                #[fefix(skip)]
                 lifetime: PhantomData<&'a ()>,
                 {fields}
             }}
@@ -51,6 +76,7 @@ pub fn message(dict: Dictionary, message: Message, custom_derive_line: &str) ->
             }}
             "#
         ),
+        aux_structs = aux_structs,
         custom_derive_line = custom_derive_line,
         identifier = identifier,
         msg_type = message.msg_type(),
@@ -58,7 +84,7 @@ pub fn message(dict: Dictionary, message: Message, custom_derive_line: &str) ->
     )
 }
 
-pub fn field_def(field: Field, fefix_path: &str) -> String {
+pub fn field_def(field: Field, fefix_path: &str, version: &str) -> String {
     let name = field.name().to_shouty_snake_case();
     let tag = field.tag().to_string();
     let (enum_type_name, enum_variants) = if let Some(variants) = field.enums() {
@@ -107,7 +133,7 @@ pub fn field_def(field: Field, fefix_path: &str) -> String {
         indoc!(
             r#"
             /// Field attributes for [`{name} <{tag}>`]
-            /// (https://www.onixs.biz/fix-dictionary/{major}.{minor}/tagnum_{tag}.html).
+            /// ({onixs_link}).
             pub const {identifier}: &FieldDef<'static, {type_param}> = &FieldDef{{
                 name: "{name}",
                 tag: unsafe {{ TagU16::new_unchecked({tag}) }},
@@ -119,8 +145,7 @@ pub fn field_def(field: Field, fefix_path: &str) -> String {
             {enum_variants}
             "#
         ),
-        major = "4",
-        minor = "4",
+        onixs_link = field.doc_url_onixs(version),
         identifier = name,
         type_param = suggested_type(
             field.tag(),
@@ -136,12 +161,26 @@ pub fn field_def(field: Field, fefix_path: &str) -> String {
     )
 }
 
-pub fn fields(dict: Dictionary, fefix_path: &str) -> String {
+pub fn fields(dict: Dictionary, fefix_path: &str, component_encoding: ComponentEncoding) -> String {
+    let version = dict.get_version().to_string();
     let field_defs = dict
         .iter_fields()
-        .map(|field| field_def(field, fefix_path))
+        .map(|field| field_def(field, fefix_path, &version))
         .collect::<Vec<String>>()
         .join("\n");
+    // Components are shared building blocks reused by several messages, so
+    // in `Nested` mode they're generated once here rather than once per
+    // message, and every message just references them by type. In
+    // `Flatten` mode there's no component struct to emit at all: each
+    // message splices the component's fields into itself.
+    let component_structs = if component_encoding == ComponentEncoding::Nested {
+        dict.iter_components()
+            .map(|component| dict.build_component_struct(component, component_encoding))
+            .collect::<Vec<String>>()
+            .join("\n")
+    } else {
+        String::new()
+    };
     let code = format!(
         indoc!(
             r#"
@@ -154,24 +193,82 @@ pub fn fields(dict: Dictionary, fefix_path: &str) -> String {
             use {fefix_path}::{{FieldDef, FieldLocation, TagU16}};
             use {fefix_path}::{{DataType, Buffer}};
             {import_data_field}
+            {import_read_fields}
             use std::marker::PhantomData;
 
             {field_defs}
+
+            {component_structs}
             "#
         ),
-        version = dict.get_version(),
+        version = version,
         notice = generated_code_notice(),
         import_data_field = if fefix_path == "fefix" {
             "use fefix::DataField;"
         } else {
             "use crate::DataField;"
         },
+        // `ReadFields` is only actually used by the component structs
+        // below (field defs don't derive it), so in `Flatten` mode, where
+        // `component_structs` is empty, importing it unconditionally
+        // would be a dead `unused_imports` warning.
+        import_read_fields = if component_structs.is_empty() {
+            String::new()
+        } else if fefix_path == "fefix" {
+            "use fefix::ReadFields;".to_string()
+        } else {
+            "use crate::ReadFields;".to_string()
+        },
         field_defs = field_defs,
+        component_structs = component_structs,
         fefix_path = fefix_path,
     );
     code
 }
 
+/// Generates the message structs for every `<message>` in `dict`, as a
+/// standalone file that's meant to sit alongside the one produced by
+/// [`fields`]. Kept separate from `fields` because messages (unlike field
+/// defs and, in [`ComponentEncoding::Nested`] mode, components) aren't
+/// shared building blocks: there's one struct per message, and the file
+/// can grow large enough that checking drift on it independently is
+/// worth the split.
+pub fn messages(
+    dict: Dictionary,
+    fefix_path: &str,
+    component_encoding: ComponentEncoding,
+) -> String {
+    let version = dict.get_version().to_string();
+    let message_structs = dict
+        .clone()
+        .iter_messages()
+        .map(|msg| message(dict.clone(), msg, "", component_encoding))
+        .collect::<Vec<String>>()
+        .join("\n");
+    format!(
+        indoc!(
+            r#"
+            //! Message definitions for {version}.
+
+            #![allow(dead_code)]
+
+            {notice}
+
+            use {fefix_path}::{{Buffer, ReadFields}};
+            use std::marker::PhantomData;
+            use super::{fields_module}::*;
+
+            {message_structs}
+            "#
+        ),
+        version = version,
+        notice = generated_code_notice(),
+        fefix_path = fefix_path,
+        fields_module = version.to_snake_case(),
+        message_structs = message_structs,
+    )
+}
+
 fn suggested_type(
     tag: TagU16,
     data_type: DataType,
@@ -245,89 +342,228 @@ fn make_type_optional(required: bool, typ: String) -> String {
 }
 
 impl Dictionary {
-    //fn build_message_struct(&self, msg_type: &str) -> String {
-    //    let message = self.message_by_msgtype(msg_type).unwrap();
-    //    let fields: Vec<String> = message
-    //        .layout()
-    //        .map(|layout_item| {
-    //            self.translate_layout_item_to_struct_field(&layout_item, layout_item.required())
-    //        })
-    //        .filter(|opt| opt.is_some())
-    //        .map(|opt| opt.unwrap())
-    //        .collect();
-    //    format!(
-    //        r#"
-    //        /// Message information: {msg_name}
-    //        #[derive(Debug, Clone, ReadFields)]
-    //        #[fefix(msg_type = "{msg_type}")]
-    //        pub struct {msg_name} {{
-    //            {fields}
-    //        }}
-    //        "#,
-    //        msg_type = message.msg_type(),
-    //        msg_name = message.name(),
-    //        fields = fields.join(", ")
-    //    )
-    //}
-
-    //fn build_component_struct(&self, component: &Component) -> String {
-    //    let fields: Vec<String> = component
-    //        .items()
-    //        .map(|layout_item| {
-    //            self.translate_layout_item_to_struct_field(&layout_item, layout_item.required())
-    //        })
-    //        .filter(|opt| opt.is_some())
-    //        .map(|opt| opt.unwrap())
-    //        .collect();
-    //    format!(
-    //        r#"
-    //        /// Component information: {msg_name}
-    //        #[fefix(msg_type = "TODO")]
-    //        #[derive(Debug, Clone, ReadFields)]
-    //        pub struct {msg_name} {{
-    //            {fields}
-    //        }}
-    //        "#,
-    //        msg_name = component.name(),
-    //        fields = fields.join(", ")
-    //    )
-    //}
+    /// Turns a single layout item into the field(s) it contributes to its
+    /// parent struct — paired with the snake_case name each one is
+    /// emitted under, so callers can detect collisions — together with
+    /// any repeating-group entry structs that must be emitted alongside
+    /// it. A component in [`ComponentEncoding::Nested`] mode contributes
+    /// one field typed after it and no struct (those come from
+    /// [`fields`]). A flattened component contributes no field
+    /// of its own: its fields are spliced in directly, so it may expand
+    /// to more than one `(name, decl)` pair.
+    fn expand_layout_item(
+        &self,
+        parent_name: &str,
+        item: &LayoutItem,
+        component_encoding: ComponentEncoding,
+    ) -> (Vec<(String, String)>, String) {
+        match item.kind() {
+            LayoutItemKind::Group(leader, inner_items) => {
+                let mut aux_structs = self.build_group_entry_struct(
+                    parent_name,
+                    &leader,
+                    &inner_items,
+                    component_encoding,
+                );
+                aux_structs.push('\n');
+                let fields = self
+                    .translate_layout_item_to_struct_field(parent_name, item, item.required())
+                    .map(|decl| (layout_item_field_name(item), decl))
+                    .into_iter()
+                    .collect();
+                (fields, aux_structs)
+            }
+            LayoutItemKind::Component(c) if component_encoding == ComponentEncoding::Flatten => {
+                self.expand_layout_items(parent_name, c.items(), component_encoding)
+            }
+            _ => {
+                let fields = self
+                    .translate_layout_item_to_struct_field(parent_name, item, item.required())
+                    .map(|decl| (layout_item_field_name(item), decl))
+                    .into_iter()
+                    .collect();
+                (fields, String::new())
+            }
+        }
+    }
+
+    /// Runs [`Self::expand_layout_item`] over a whole layout, joining the
+    /// field declarations and auxiliary structs it produces. Panics if
+    /// flattening components (or a component and a sibling field) would
+    /// produce two fields with the same name, since that's a duplicate
+    /// struct field — a compile error `syn::parse_file` can't catch, as
+    /// it's semantic rather than syntactic.
+    fn expand_layout_items(
+        &self,
+        parent_name: &str,
+        items: impl Iterator<Item = LayoutItem>,
+        component_encoding: ComponentEncoding,
+    ) -> (String, String) {
+        let mut aux_structs = String::new();
+        let mut seen_names = std::collections::HashSet::new();
+        let fields = items
+            .flat_map(|item| {
+                let (item_fields, aux) =
+                    self.expand_layout_item(parent_name, &item, component_encoding);
+                aux_structs.push_str(&aux);
+                item_fields
+            })
+            .map(|(name, decl)| {
+                assert!(
+                    seen_names.insert(name.clone()),
+                    "`{}` would generate two fields named `{}` (likely two flattened \
+                     components, or a component and a field, sharing a name); rename one \
+                     of them or switch to `ComponentEncoding::Nested`",
+                    parent_name,
+                    name,
+                );
+                decl
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        (fields, aux_structs)
+    }
 
     fn translate_layout_item_to_struct_field(
         &self,
+        parent_name: &str,
         item: &LayoutItem,
         required: bool,
     ) -> Option<String> {
-        let field_name = match item.kind() {
-            LayoutItemKind::Component(c) => c.name().to_snake_case(),
-            LayoutItemKind::Group(_, _) => return None,
-            LayoutItemKind::Field(f) => f.name().to_snake_case(),
-        };
+        let field_name = layout_item_field_name(item);
         let field_type = match item.kind() {
-            LayoutItemKind::Component(_c) => "()".to_string(),
-            LayoutItemKind::Group(_, _) => "()".to_string(),
+            LayoutItemKind::Component(c) => format!("{}<'a>", c.name().to_camel_case()),
+            LayoutItemKind::Group(leader, _) => {
+                format!("Vec<{}<'a>>", group_entry_identifier(parent_name, &leader))
+            }
             LayoutItemKind::Field(f) => {
                 suggested_type_with_lifetime(f.tag(), f.data_type().basetype()).to_string()
             }
         };
-        //let field_tag = match item.kind() {
-        //    LayoutItemKind::Component(_c) => 1337,
-        //    LayoutItemKind::Group(_, _) => 42,
-        //    LayoutItemKind::Field(f) => f.tag(),
-        //};
-        let _field_doc = match item.kind() {
-            LayoutItemKind::Component(_c) => "///".to_string(),
-            LayoutItemKind::Group(_, _) => "///".to_string(),
+        let field_doc = match item.kind() {
+            LayoutItemKind::Component(_c) => String::new(),
+            LayoutItemKind::Group(_, _) => String::new(),
             LayoutItemKind::Field(f) => docs::gen_field(self.get_version().to_string(), &f),
         };
+        // `ReadFields` drives (de)serialization off this attribute: a
+        // component field carries no tag of its own (its tags belong to
+        // the fields nested inside it), while a field or a group is always
+        // identified by the tag of its own leader.
+        let fefix_attr = match item.kind() {
+            LayoutItemKind::Component(_c) => "#[fefix(component)]".to_string(),
+            LayoutItemKind::Group(leader, _) => {
+                format!("#[fefix(tag = {}, required = {})]", leader.tag(), required)
+            }
+            LayoutItemKind::Field(f) => {
+                format!("#[fefix(tag = {}, required = {})]", f.tag(), required)
+            }
+        };
+        // The "NoXxx" count field of a repeating group is never emitted as a
+        // standalone `usize`: its value is implied by the length of the
+        // `Vec` below, so readers/writers must derive it rather than trust
+        // a field that could desync from the actual entry count.
         Some(format!(
             r#"
+            {field_doc}
+            {fefix_attr}
             pub {identifier}: {field_type},
             "#,
+            field_doc = field_doc,
+            fefix_attr = fefix_attr,
             identifier = field_name,
             field_type = make_type_optional(required, field_type)
         ))
     }
+
+    /// Builds the `<Parent><GroupName>Entry<'a>` struct for a repeating
+    /// group, recursively expanding any groups or (in `Flatten` mode)
+    /// components nested within it.
+    fn build_group_entry_struct(
+        &self,
+        parent_name: &str,
+        leader: &Field,
+        items: &[LayoutItem],
+        component_encoding: ComponentEncoding,
+    ) -> String {
+        let entry_identifier = group_entry_identifier(parent_name, leader);
+        let (fields, aux_structs) =
+            self.expand_layout_items(&entry_identifier, items.iter().cloned(), component_encoding);
+        format!(
+            indoc!(
+                r#"
+                {aux_structs}
+                #[derive(Debug, Clone, ReadFields)]
+                pub struct {entry_identifier}<'a> {{
+                    #[fefix(skip)]
+                    lifetime: PhantomData<&'a ()>,
+                    {fields}
+                }}
+                "#
+            ),
+            aux_structs = aux_structs,
+            entry_identifier = entry_identifier,
+            fields = fields,
+        )
+    }
+
+    /// Builds the struct for a `<component>` block, used in
+    /// [`ComponentEncoding::Nested`] mode. Unlike repeating groups,
+    /// components are shared building blocks reused by several messages,
+    /// so the struct name isn't parent-qualified.
+    fn build_component_struct(
+        &self,
+        component: Component,
+        component_encoding: ComponentEncoding,
+    ) -> String {
+        let identifier = component.name().to_camel_case();
+        let (fields, aux_structs) =
+            self.expand_layout_items(&identifier, component.items(), component_encoding);
+        format!(
+            indoc!(
+                r#"
+                {aux_structs}
+                #[derive(Debug, Clone, ReadFields)]
+                pub struct {identifier}<'a> {{
+                    #[fefix(skip)]
+                    lifetime: PhantomData<&'a ()>,
+                    {fields}
+                }}
+                "#
+            ),
+            aux_structs = aux_structs,
+            identifier = identifier,
+            fields = fields,
+        )
+    }
+}
+
+/// Name of the repeating group led by `leader`, with the "NoXxx" counting
+/// prefix stripped (e.g. `NoMDEntries` becomes `MDEntries`).
+fn group_name(leader: &Field) -> &str {
+    leader.name().strip_prefix("No").unwrap_or(leader.name())
+}
+
+/// The snake_case name a layout item is emitted under in its parent
+/// struct. The single source of truth for this, so naming can't drift
+/// between the field declaration itself and anything (e.g. collision
+/// detection) that needs to know the name ahead of generating it.
+fn layout_item_field_name(item: &LayoutItem) -> String {
+    match item.kind() {
+        LayoutItemKind::Component(c) => c.name().to_snake_case(),
+        // The leader's own name is the "NoXxx" count field (e.g.
+        // `NoMDEntries`), but the field holds the entries themselves, not
+        // the count, so it's named after the group (`md_entries`) rather
+        // than the leader verbatim.
+        LayoutItemKind::Group(leader, _) => group_name(&leader).to_snake_case(),
+        LayoutItemKind::Field(f) => f.name().to_snake_case(),
+    }
+}
+
+/// Name of the struct generated for the entries of the repeating group led
+/// by `leader` (e.g. `NoMDEntries` within `MarketDataSnapshotFullRefresh`
+/// becomes `MarketDataSnapshotFullRefreshMdEntriesEntry`).
+fn group_entry_identifier(parent_name: &str, leader: &Field) -> String {
+    format!("{}{}Entry", parent_name, group_name(leader).to_camel_case())
 }
 
 mod docs {
@@ -348,6 +584,77 @@ mod docs {
     }
 }
 
+// Sourcegen support, following the pattern used by rust-analyzer's
+// `sourcegen_ast`: generated code is rustfmt-ed and checked into version
+// control, and a test fails (after rewriting the file) whenever the
+// checked-in output drifts from what the dictionary would produce.
+#[cfg(test)]
+mod sourcegen {
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::process::{Command, Stdio};
+
+    /// Shells out to `rustfmt` to normalize generated code, since the
+    /// `indoc!` templates in this module only produce ragged indentation.
+    pub fn reformat(code: String) -> String {
+        let mut rustfmt = Command::new("rustfmt")
+            .args(&["--edition", "2018"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to spawn `rustfmt`; is it installed and on $PATH?");
+        rustfmt
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(code.as_bytes())
+            .unwrap();
+        let output = rustfmt.wait_with_output().unwrap();
+        assert!(
+            output.status.success(),
+            "rustfmt failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    /// Writes `contents` to `path` only if they differ from what's already
+    /// there, then panics so that CI (and anyone running the tests
+    /// locally) notices the checked-in file was out of date.
+    pub fn ensure_file_contents(path: &Path, contents: &str) {
+        if let Ok(old_contents) = std::fs::read_to_string(path) {
+            if old_contents == contents {
+                return;
+            }
+        }
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        std::fs::write(path, contents)
+            .unwrap_or_else(|err| panic!("failed to write `{}`: {}", path.display(), err));
+        panic!(
+            "`{}` is out of date; it was just regenerated, rerun the tests and commit the result",
+            path.display()
+        );
+    }
+
+    pub fn generated_file_path(dict_version: &str) -> PathBuf {
+        use heck::SnakeCase;
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("generated")
+            .join(format!("{}.rs", dict_version.to_snake_case()))
+    }
+
+    pub fn generated_messages_file_path(dict_version: &str) -> PathBuf {
+        use heck::SnakeCase;
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("generated")
+            .join(format!("{}_messages.rs", dict_version.to_snake_case()))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -356,16 +663,196 @@ mod test {
     #[test]
     fn fix_v42_syntax() {
         let fix_v42 = Dictionary::from_version(AppVersion::Fix42);
-        let code = fields(fix_v42, "fefix");
+        let code = fields(fix_v42, "fefix", ComponentEncoding::Nested);
         assert!(syn::parse_file(code.as_str()).is_ok());
     }
 
+    /// Regression test for a prior bug where `field_def` hardcoded the FIX
+    /// 4.4 OnixS doc link for every dictionary version. The doc comment
+    /// must embed the actual per-version link `doc_url_onixs` builds for
+    /// `dict.get_version()`, not a stale constant.
+    #[test]
+    fn field_doc_link_is_dictionary_driven() {
+        let fix42 = Dictionary::from_version(AppVersion::Fix42);
+        let version = fix42.get_version().to_string();
+        let field = fix42
+            .iter_fields()
+            .next()
+            .expect("dictionary has no fields");
+        let onixs_link = field.doc_url_onixs(&version);
+        let code = field_def(field, "crate", &version);
+        assert!(
+            code.contains(onixs_link.as_str()),
+            "field doc comment must embed the dictionary's own OnixS link `{}`:\n{}",
+            onixs_link,
+            code
+        );
+        assert!(
+            version.contains("4.4") || !code.contains("4.4"),
+            "field_def must not hardcode the FIX 4.4 doc link for a {} field:\n{}",
+            version,
+            code
+        );
+    }
+
     #[test]
     fn syntax_of_field_tags_is_ok() {
         for version in AppVersion::ALL.iter().copied() {
             let dict = Dictionary::from_version(version);
-            let code = fields(dict, "crate");
+            let code = fields(dict, "crate", ComponentEncoding::Nested);
             syn::parse_file(code.as_str()).unwrap();
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn syntax_of_flattened_fields_is_ok() {
+        for version in AppVersion::ALL.iter().copied() {
+            let dict = Dictionary::from_version(version);
+            let code = fields(dict, "crate", ComponentEncoding::Flatten);
+            syn::parse_file(code.as_str())
+                .unwrap_or_else(|err| panic!("invalid Rust:\n{}\n\n{}", code, err));
+            // `Flatten` mode emits no component structs, so the
+            // unconditional `ReadFields` import would otherwise be dead.
+            assert!(
+                !code.contains("ReadFields"),
+                "flattened fields() has no use for `ReadFields` but imports it anyway:\n{}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn syntax_of_messages_with_groups_is_ok() {
+        for version in AppVersion::ALL.iter().copied() {
+            let dict = Dictionary::from_version(version);
+            for msg in dict.clone().iter_messages() {
+                let code = message(dict.clone(), msg, "", ComponentEncoding::Nested);
+                assert!(
+                    syn::parse_file(code.as_str()).is_ok(),
+                    "generated code for message is not valid Rust:\n{}",
+                    code
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn syntax_of_messages_with_flattened_components_is_ok() {
+        for version in AppVersion::ALL.iter().copied() {
+            let dict = Dictionary::from_version(version);
+            for msg in dict.clone().iter_messages() {
+                let code = message(dict.clone(), msg, "", ComponentEncoding::Flatten);
+                let parsed = syn::parse_file(code.as_str()).unwrap_or_else(|err| {
+                    panic!(
+                        "generated code for message is not valid Rust:\n{}\n\n{}",
+                        code, err
+                    )
+                });
+                // `syn::parse_file` only proves the file is syntactically
+                // valid Rust; it can't catch two flattened components (or a
+                // component and a sibling field) landing on the same field
+                // name, which is a semantic error (duplicate struct field)
+                // rather than a parse error. Check every generated struct's
+                // fields are uniquely named so a real collision fails this
+                // test loudly instead of silently producing uncompilable
+                // output.
+                for item in &parsed.items {
+                    if let syn::Item::Struct(s) = item {
+                        let mut seen = std::collections::HashSet::new();
+                        for field in &s.fields {
+                            let name = field.ident.as_ref().unwrap().to_string();
+                            assert!(
+                                seen.insert(name.clone()),
+                                "struct `{}` has two fields named `{}`:\n{}",
+                                s.ident,
+                                name,
+                                code
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn messages_are_tagged_for_read_fields() {
+        for version in AppVersion::ALL.iter().copied() {
+            let dict = Dictionary::from_version(version);
+            for msg in dict.clone().iter_messages() {
+                let msg_type = msg.msg_type().to_string();
+                let identifier = msg.name().to_camel_case();
+                let code = message(dict.clone(), msg, "", ComponentEncoding::Nested);
+                assert!(
+                    code.contains("ReadFields"),
+                    "message struct is missing the `ReadFields` derive:\n{}",
+                    code
+                );
+                assert!(
+                    code.contains(&format!(r#"#[fefix(msg_type = "{}")]"#, msg_type)),
+                    "message struct is missing its `#[fefix(msg_type = ...)]` attribute:\n{}",
+                    code
+                );
+                let parsed = syn::parse_file(code.as_str())
+                    .unwrap_or_else(|err| panic!("invalid Rust:\n{}\n\n{}", code, err));
+                let item_struct = parsed
+                    .items
+                    .iter()
+                    .find_map(|item| match item {
+                        syn::Item::Struct(s) if s.ident == identifier.as_str() => Some(s),
+                        _ => None,
+                    })
+                    .expect("message struct not found in generated file");
+                assert!(
+                    item_struct.fields.iter().all(|f| f.ident.is_some()),
+                    "every field of a message struct must be named, to be addressable by `ReadFields`"
+                );
+                // `ReadFields` needs every field tagged with its own
+                // `#[fefix(...)]` attribute to know how to read it — the
+                // phantom lifetime field included, via `#[fefix(skip)]`,
+                // since it isn't FIX data. A field with no `fefix` attribute
+                // at all would only surface as a macro-expansion error, which
+                // `syn::parse_file` can't see, so check for it here instead.
+                for field in &item_struct.fields {
+                    assert!(
+                        field.attrs.iter().any(|attr| attr.path.is_ident("fefix")),
+                        "field `{}` on `{}` has no `#[fefix(...)]` attribute:\n{}",
+                        field.ident.as_ref().unwrap(),
+                        identifier,
+                        code
+                    );
+                }
+            }
+        }
+    }
+
+    /// Regenerates the committed field definitions for every FIX version
+    /// and fails if the checked-in files are out of date, per
+    /// rust-analyzer's `sourcegen_ast` pattern. Run this test (it rewrites
+    /// the files itself) and commit the result whenever the dictionary
+    /// changes.
+    #[test]
+    fn sourcegen_fields_are_up_to_date() {
+        for version in AppVersion::ALL.iter().copied() {
+            let dict = Dictionary::from_version(version);
+            let version_name = dict.get_version().to_string();
+            let code = sourcegen::reformat(fields(dict, "crate", ComponentEncoding::Nested));
+            let path = sourcegen::generated_file_path(&version_name);
+            sourcegen::ensure_file_contents(&path, &code);
+        }
+    }
+
+    /// Same as [`sourcegen_fields_are_up_to_date`], but for the message
+    /// structs produced by [`messages`] — the other half of "generated
+    /// field/message code" this sourcegen workflow is meant to cover.
+    #[test]
+    fn sourcegen_messages_are_up_to_date() {
+        for version in AppVersion::ALL.iter().copied() {
+            let dict = Dictionary::from_version(version);
+            let version_name = dict.get_version().to_string();
+            let code = sourcegen::reformat(messages(dict, "crate", ComponentEncoding::Nested));
+            let path = sourcegen::generated_messages_file_path(&version_name);
+            sourcegen::ensure_file_contents(&path, &code);
+        }
+    }
+}